@@ -0,0 +1,115 @@
+use bytes::BytesMut;
+
+/// Where a data packet sits within its message. A message that fits in one
+/// packet is `Only`; a split message's packets are `First`, zero or more
+/// `Middle`, then `Last`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageLocation {
+    Only,
+    First,
+    Middle,
+    Last,
+}
+
+impl MessageLocation {
+    pub fn is_first(self) -> bool {
+        match self {
+            MessageLocation::Only | MessageLocation::First => true,
+            MessageLocation::Middle | MessageLocation::Last => false,
+        }
+    }
+
+    pub fn is_last(self) -> bool {
+        match self {
+            MessageLocation::Only | MessageLocation::Last => true,
+            MessageLocation::First | MessageLocation::Middle => false,
+        }
+    }
+}
+
+/// https://tools.ietf.org/html/draft-gg-udt-03#page-12
+#[derive(Clone, Debug)]
+pub enum Packet {
+    Control {
+        timestamp: i32,
+        dest_sockid: i32,
+        control_type: ControlTypes,
+    },
+    Data {
+        seq_number: i32,
+        message_loc: MessageLocation,
+        in_order_delivery: bool,
+        message_number: i32,
+        timestamp: i32,
+        dest_sockid: i32,
+        payload: BytesMut,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HandshakeInfo;
+
+#[derive(Clone, Debug)]
+pub enum ControlTypes {
+    Handshake(HandshakeInfo),
+    KeepAlive,
+    Ack(i32, AckControlInfo),
+    Ack2(i32),
+    Nak(NakControlInfo),
+    DropRequest(i32, DropRequestInfo),
+    Shutdown,
+}
+
+/// https://tools.ietf.org/html/draft-gg-udt-03#page-12
+#[derive(Clone, Copy, Debug)]
+pub struct AckControlInfo {
+    pub lrsn: i32,
+    pub rtt: Option<i32>,
+    pub rtt_var: Option<i32>,
+}
+
+impl AckControlInfo {
+    /// A light ACK: sequence number only, no RTT/window fields.
+    pub fn new_light(lrsn: i32) -> AckControlInfo {
+        AckControlInfo {
+            lrsn,
+            rtt: None,
+            rtt_var: None,
+        }
+    }
+
+    /// A full ACK, carrying the receiver's RTT/RTTVar estimate.
+    pub fn new_full(lrsn: i32, rtt: i32, rtt_var: i32) -> AckControlInfo {
+        AckControlInfo {
+            lrsn,
+            rtt: Some(rtt),
+            rtt_var: Some(rtt_var),
+        }
+    }
+}
+
+/// https://tools.ietf.org/html/draft-gg-udt-03#page-12
+/// the wire format used for `ControlTypes::Nak`; see
+/// `Receiver::compress_loss_list`.
+#[derive(Clone, Debug)]
+pub struct NakControlInfo {
+    pub loss_info: Vec<i32>,
+}
+
+impl NakControlInfo {
+    pub fn new(loss_info: Vec<i32>) -> NakControlInfo {
+        NakControlInfo { loss_info }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DropRequestInfo {
+    pub first: i32,
+    pub last: i32,
+}
+
+impl DropRequestInfo {
+    pub fn new(first: i32, last: i32) -> DropRequestInfo {
+        DropRequestInfo { first, last }
+    }
+}
@@ -3,10 +3,62 @@ use std::time::Duration;
 use std::net::SocketAddr;
 
 use socket::SrtSocket;
-use packet::{AckControlInfo, ControlTypes, Packet};
+use packet::{AckControlInfo, ControlTypes, DropRequestInfo, NakControlInfo, Packet};
 use bytes::BytesMut;
 use futures::prelude::*;
-use futures_timer::Interval;
+use futures_timer::{Delay, Interval};
+
+/// How long to wait between checking the loss list for entries that need
+/// to be fed back into a new NAK; an entry is re-sent once `rtt` has passed
+/// since its last feedback.
+/// https://tools.ietf.org/html/draft-gg-udt-03#page-12
+const DEFAULT_NAK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The initial RTT estimate (in us) to use before the first ACK/ACK2
+/// round trip has completed.
+const DEFAULT_RTT: i32 = 100_000;
+
+/// How far, in sequence numbers, `lrsn` must have moved past a missing
+/// packet before it is declared lost instead of just reordered, modeled on
+/// the packet-threshold approach from QUIC loss recovery.
+const REORDER_THRESHOLD: i32 = 3;
+
+/// How many entries to keep in `ack_history_window` before the oldest
+/// unacknowledged ACK is evicted.
+const ACK_HISTORY_SIZE: usize = 512;
+
+/// How many data packets to receive between light ACKs.
+const DEFAULT_LIGHT_ACK_PACKET_INTERVAL: i32 = 64;
+
+/// How often to consider sending a full ACK, modeled on the delayed-ACK
+/// intervals used by TCP stacks.
+const DEFAULT_FULL_ACK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The floor, in us, for the base of the EXP backoff (`4*rtt + rtt_var`).
+const MIN_EXP_INTERVAL_US: i32 = 100_000;
+
+/// The ceiling for the EXP backoff, so a long-stalled link doesn't end up
+/// waiting minutes between keep-alives.
+const MAX_EXP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How many consecutive unanswered EXP timeouts before the peer is
+/// considered dead.
+const EXP_DEAD_COUNT: i32 = 16;
+
+/// The minimum amount of wall-clock time that must have passed since the
+/// first unanswered EXP before the connection is declared dead, so a burst
+/// of very short backoff intervals can't trip EXP_DEAD_COUNT prematurely.
+const EXP_DEAD_MIN_ELAPSED_US: i32 = 5_000_000;
+
+/// How often to re-check the TSBPD buffer for packets whose playout
+/// deadline has passed, even if no new data has arrived.
+const TSBPD_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The ACK number reserved for light ACKs. Never recorded in
+/// `ack_history_window` (full ACKs are numbered from 1), so an ACK2 a peer
+/// sends for one (it shouldn't, but nothing stops it) matches no entry and
+/// can't poison the RTT estimate.
+const LIGHT_ACK_NUM: i32 = 0;
 
 struct LossListEntry {
     seq_num: i32,
@@ -16,7 +68,26 @@ struct LossListEntry {
     k: i32,
 }
 
+/// A data packet sitting in the TSBPD buffer, waiting for its playout
+/// deadline and/or the rest of its message to arrive.
+struct BufferedPacket {
+    seq_number: i32,
+    message_number: i32,
+
+    /// whether this is the first/last packet of its message; a message
+    /// that fits in one packet is both
+    first: bool,
+    last: bool,
+
+    /// `packet_origin_time + tsbpd_latency`: when this packet becomes
+    /// eligible for release
+    release_time: i32,
+
+    payload: BytesMut,
+}
+
 pub struct Receiver {
+    sock: SrtSocket,
     remote: SocketAddr,
 
     /// https://tools.ietf.org/html/draft-gg-udt-03#page-12
@@ -27,6 +98,15 @@ pub struct Receiver {
     /// the increasing order of packet sequence numbers.
     loss_list: Vec<LossListEntry>,
 
+    /// Sequence numbers that are missing but that `lrsn` hasn't yet moved
+    /// `reorder_threshold` past, so they're assumed to just be reordered
+    /// rather than lost. Kept in increasing order.
+    suspected_loss_list: Vec<i32>,
+
+    /// how far, in sequence numbers, `lrsn` must be past a suspected-missing
+    /// packet before it is promoted into `loss_list` and NAKed
+    reorder_threshold: i32,
+
     /// https://tools.ietf.org/html/draft-gg-udt-03#page-12
     /// ACK History Window: A circular array of each sent ACK and the time
     /// it is sent out. The most recent value will overwrite the oldest
@@ -38,29 +118,426 @@ pub struct Receiver {
     /// of each data packet.
     packet_history_window: Vec<(i32, i32)>,
 
-    /// Tells the receiver to ACK the sender
-    ack_timer: Interval,
+    /// Tells the receiver to consider sending a full ACK
+    full_ack_timer: Interval,
+
+    /// Tells the receiver to re-check the loss list and NAK any entries
+    /// that haven't been acknowledged in roughly an RTT.
+    nak_timer: Interval,
+
+    /// Tells the receiver to send a keep-alive because nothing has been
+    /// heard from the peer in a while. Reset on every received packet, and
+    /// rescheduled with exponential backoff each time it fires.
+    exp_timer: Delay,
+
+    /// consecutive EXP timeouts without any packet from the peer
+    exp_count: i32,
+
+    /// the timestamp of the first unanswered EXP in the current run,
+    /// used to gate dead-peer detection on wall-clock time as well as count
+    exp_run_start: i32,
+
+    /// how many data packets to receive between light ACKs
+    light_ack_packet_interval: i32,
+
+    /// how many data packets have arrived since the last light ACK
+    packets_since_light_ack: i32,
+
+    /// the `lrsn` as of the last full ACK that was actually sent, used to
+    /// suppress a scheduled full ACK when nothing new has arrived
+    last_full_ack_lrsn: i32,
+
+    /// the current RTT estimate, in us. Updated once ACK/ACK2 round trips
+    /// start completing; see the RTT estimation logic.
+    rtt: i32,
+
+    /// the current RTT variance estimate, in us
+    rtt_var: i32,
 
-    /// the highest received packet sequence number
+    /// whether `rtt`/`rtt_var` have been seeded by a real ACK/ACK2 sample yet
+    rtt_sampled: bool,
+
+    /// the highest received packet sequence number; seeded from the
+    /// handshake's negotiated initial sequence number minus one, so the
+    /// first data packet doesn't look like a gap from 0
     lrsn: i32,
 
+    /// the number the next full ACK will be sent with; starts at 1, since
+    /// `LIGHT_ACK_NUM` permanently reserves 0 for light ACKs
     next_ack: i32,
+
+    /// TSBPD reorder buffer: packets that have arrived but haven't yet been
+    /// released to the `Stream` consumer, kept sorted by `seq_number`.
+    buffer: Vec<BufferedPacket>,
+
+    /// the negotiated TSBPD latency, in us; see `latency_exchange`
+    tsbpd_latency: i32,
+
+    /// whether messages must be delivered in sequence order; renegotiated
+    /// from every data packet's `in_order_delivery` flag
+    in_order_delivery: bool,
+
+    /// the sequence number of the last packet released to the consumer;
+    /// seeded from the negotiated ISN minus one, same as `lrsn`, so the
+    /// first buffered packet isn't mistaken for a head-of-buffer gap
+    last_released_seq: i32,
+
+    /// Tells the receiver to recheck the TSBPD buffer for releasable or
+    /// too-late packets, independent of new data arriving.
+    tsbpd_timer: Interval,
 }
 
 impl Receiver {
     pub fn new(sock: SrtSocket, remote: SocketAddr) -> Receiver {
+        // `Settings::reorder_threshold` (and friends) override these when
+        // set; fall back to the defaults otherwise
+        let reorder_threshold = sock.settings().reorder_threshold.unwrap_or(REORDER_THRESHOLD);
+        let light_ack_packet_interval = sock
+            .settings()
+            .light_ack_packet_interval
+            .unwrap_or(DEFAULT_LIGHT_ACK_PACKET_INTERVAL);
+        let full_ack_interval = sock
+            .settings()
+            .full_ack_interval
+            .unwrap_or(DEFAULT_FULL_ACK_INTERVAL);
+
+        Receiver::with_config(
+            sock,
+            remote,
+            reorder_threshold,
+            light_ack_packet_interval,
+            full_ack_interval,
+        )
+    }
+
+    /// Construct a `Receiver` with non-default tuning; see
+    /// `SrtSocketBuilder::reorder_threshold`, `light_ack_packet_interval`,
+    /// and `full_ack_interval`.
+    pub fn with_config(
+        sock: SrtSocket,
+        remote: SocketAddr,
+        reorder_threshold: i32,
+        light_ack_packet_interval: i32,
+        full_ack_interval: Duration,
+    ) -> Receiver {
+        let tsbpd_latency = sock.settings().tsbpd_latency.as_micros() as i32;
+
+        // the peer's first data packet will carry this sequence number, not
+        // 0; seeding `lrsn` any other way treats the entire range between 0
+        // and the ISN as lost on the very first packet
+        let isn = sock.settings().init_seq_num;
+
         Receiver {
             sock,
             remote,
+            buffer: Vec::new(),
+            tsbpd_latency,
+            in_order_delivery: true,
+            last_released_seq: isn - 1,
+            tsbpd_timer: Interval::new(TSBPD_CHECK_INTERVAL),
             loss_list: Vec::new(),
+            suspected_loss_list: Vec::new(),
+            reorder_threshold,
             ack_history_window: Vec::new(),
             packet_history_window: Vec::new(),
-            // TODO: what's the actual ACK timeout?
-            ack_timer: Interval::new(Duration::from_secs(1)),
-            lrsn: 0,
-            next_ack: 0,
+            full_ack_timer: Interval::new(full_ack_interval),
+            nak_timer: Interval::new(DEFAULT_NAK_INTERVAL),
+            exp_timer: Delay::new(Self::exp_backoff(DEFAULT_RTT, 0, 0)),
+            exp_count: 0,
+            exp_run_start: 0,
+            light_ack_packet_interval,
+            packets_since_light_ack: 0,
+            last_full_ack_lrsn: isn - 1,
+            rtt: DEFAULT_RTT,
+            rtt_var: 0,
+            rtt_sampled: false,
+            lrsn: isn - 1,
+            next_ack: 1,
+        }
+    }
+
+    /// Compute the next EXP backoff: `max(4*rtt + rtt_var, min_exp)`,
+    /// doubling for every consecutive expiry, capped at `MAX_EXP_INTERVAL`.
+    fn exp_backoff(rtt: i32, rtt_var: i32, exp_count: i32) -> Duration {
+        let base_us = i64::from((4 * rtt + rtt_var).max(MIN_EXP_INTERVAL_US));
+        let backoff_us = base_us.saturating_mul(1i64 << exp_count.min(20));
+
+        Duration::from_micros(backoff_us as u64).min(MAX_EXP_INTERVAL)
+    }
+
+    /// Record that a full ACK was sent, so the matching `Ack2` can later be
+    /// turned into an RTT sample. Evicts the oldest entry once
+    /// `ack_history_window` is full, per the circular-array semantics
+    /// described on the field.
+    fn record_ack(&mut self, ack_num: i32, send_time: i32) {
+        if self.ack_history_window.len() >= ACK_HISTORY_SIZE {
+            self.ack_history_window.remove(0);
+        }
+
+        self.ack_history_window.push((ack_num, send_time));
+    }
+
+    /// Fold a new RTT sample (the time between sending a full ACK and
+    /// receiving the matching ACK2) into the smoothed RTT/RTTVar estimate.
+    fn sample_rtt(&mut self, sample: i32) {
+        let (rtt, rtt_var) = Self::ewma_rtt(self.rtt, self.rtt_var, self.rtt_sampled, sample);
+
+        self.rtt = rtt;
+        self.rtt_var = rtt_var;
+        self.rtt_sampled = true;
+    }
+
+    /// The EWMA update itself, pulled out of `sample_rtt` so it can be
+    /// exercised without a real `Receiver` (which needs a live `SrtSocket`).
+    fn ewma_rtt(rtt: i32, rtt_var: i32, rtt_sampled: bool, sample: i32) -> (i32, i32) {
+        if rtt_sampled {
+            let rtt_var = (3 * rtt_var + (rtt - sample).abs()) / 4;
+            let rtt = (7 * rtt + sample) / 8;
+
+            (rtt, rtt_var)
+        } else {
+            (sample, sample / 2)
         }
     }
+
+    /// Compress a sorted, deduplicated list of sequence numbers into the
+    /// wire format used by `ControlTypes::Nak`: a run of two or more
+    /// consecutive sequence numbers is encoded as a single
+    /// `(lo | 0x80000000, hi)` pair, everything else is encoded as-is.
+    fn compress_loss_list(seqs: &[i32]) -> Vec<i32> {
+        let mut out = Vec::new();
+
+        let mut i = 0;
+        while i < seqs.len() {
+            let start = seqs[i];
+            let mut end = start;
+            let mut j = i + 1;
+
+            while j < seqs.len() && seqs[j] == end + 1 {
+                end = seqs[j];
+                j += 1;
+            }
+
+            if end > start {
+                out.push(start | 0x8000_0000u32 as i32);
+                out.push(end);
+            } else {
+                out.push(start);
+            }
+
+            i = j;
+        }
+
+        out
+    }
+
+    /// Send a lightweight ACK: sequence number only, no RTT/window fields.
+    ///
+    /// Always uses `LIGHT_ACK_NUM` rather than a real full-ACK number: that
+    /// number is never recorded in `ack_history_window`, so even if a peer
+    /// sends an ACK2 back for it, it won't match a `record_ack` entry and
+    /// won't be turned into an (incorrect) RTT sample. Reusing an actual
+    /// full ACK's number here would let a light-ack-triggered ACK2 get
+    /// attributed to that full ACK's send time instead.
+    fn send_light_ack(&mut self) {
+        let ack = Packet::Control {
+            timestamp: self.sock.get_timestamp(),
+            dest_sockid: 0, // TODO: this should be better
+            control_type: ControlTypes::Ack(LIGHT_ACK_NUM, AckControlInfo::new_light(self.lrsn)),
+        };
+
+        self.sock.queue_sender.send((ack, self.remote)).unwrap();
+    }
+
+    /// Send a keep-alive, used to probe a peer that's gone quiet.
+    fn send_keep_alive(&mut self) {
+        let keep_alive = Packet::Control {
+            timestamp: self.sock.get_timestamp(),
+            dest_sockid: 0, // TODO: this should be better
+            control_type: ControlTypes::KeepAlive,
+        };
+
+        self.sock
+            .queue_sender
+            .send((keep_alive, self.remote))
+            .unwrap();
+    }
+
+    /// Send a `ControlTypes::DropRequest` covering `first..=last` and prune
+    /// any loss-list bookkeeping for that range, since those packets are
+    /// being given up on rather than waited for.
+    fn drop_through(&mut self, first: i32, last: i32) {
+        if first > last {
+            return;
+        }
+
+        let drop_request = Packet::Control {
+            timestamp: self.sock.get_timestamp(),
+            dest_sockid: 0, // TODO: this should be better
+            // TODO: we don't always know the message number of a range that
+            // was never received at all; 0 is a sentinel for "by sequence"
+            control_type: ControlTypes::DropRequest(0, DropRequestInfo::new(first, last)),
+        };
+
+        self.sock
+            .queue_sender
+            .send((drop_request, self.remote))
+            .unwrap();
+
+        self.loss_list
+            .retain(|e| e.seq_num < first || e.seq_num > last);
+        self.suspected_loss_list
+            .retain(|&seq| seq < first || seq > last);
+
+        self.last_released_seq = last;
+    }
+
+    /// Concatenate the payloads of a complete message's fragments in
+    /// sequence order.
+    fn reassemble(fragments: Vec<BufferedPacket>) -> BytesMut {
+        let mut out = BytesMut::new();
+        for fragment in fragments {
+            out.unsplit(fragment.payload);
+        }
+        out
+    }
+
+    /// Try to release the next in-order message from the TSBPD buffer.
+    /// Honors message boundaries, and too-late-packet-drops a gap (or an
+    /// overdue, still-incomplete message) once its deadline has passed.
+    fn try_release_in_order(&mut self) -> Option<BytesMut> {
+        loop {
+            let now = self.sock.get_timestamp();
+            let head_seq = self.buffer.first()?.seq_number;
+
+            if head_seq != self.last_released_seq + 1 {
+                if now < self.buffer[0].release_time {
+                    return None; // still time for the retransmit to arrive
+                }
+
+                self.drop_through(self.last_released_seq + 1, head_seq - 1);
+                continue;
+            }
+
+            // walk the contiguous run starting at the head, looking for the
+            // packet that completes the message
+            let mut end = None;
+            for (i, pkt) in self.buffer.iter().enumerate() {
+                if pkt.seq_number != head_seq + i as i32 {
+                    break;
+                }
+                if pkt.last {
+                    end = Some(i);
+                    break;
+                }
+            }
+
+            let end = match end {
+                Some(end) => end,
+                None => {
+                    // message incomplete; give up on it once the packet after
+                    // the gap in it is overdue. With nothing buffered past the
+                    // run, the rest may simply not have been sent yet
+                    let last_contiguous = self
+                        .buffer
+                        .iter()
+                        .enumerate()
+                        .take_while(|&(i, pkt)| pkt.seq_number == head_seq + i as i32)
+                        .map(|(i, _)| i)
+                        .last()
+                        .unwrap();
+
+                    match self.buffer.get(last_contiguous + 1) {
+                        Some(next) if now >= next.release_time => {}
+                        _ => return None,
+                    }
+
+                    let dropped: Vec<_> = self.buffer.drain(..=last_contiguous).collect();
+                    self.drop_through(head_seq, dropped.last().unwrap().seq_number);
+                    continue;
+                }
+            };
+
+            let deadline = self.buffer[..=end].iter().map(|p| p.release_time).max().unwrap();
+            if now < deadline {
+                return None;
+            }
+
+            let fragments: Vec<_> = self.buffer.drain(..=end).collect();
+            self.last_released_seq = fragments.last().unwrap().seq_number;
+
+            return Some(Self::reassemble(fragments));
+        }
+    }
+
+    /// Try to release the earliest complete, overdue message anywhere in
+    /// the TSBPD buffer, without requiring it to be in sequence order.
+    fn try_release_out_of_order(&mut self) -> Option<BytesMut> {
+        let now = self.sock.get_timestamp();
+        let mut idx = 0;
+
+        while idx < self.buffer.len() {
+            let start = idx;
+            let start_seq = self.buffer[start].seq_number;
+            let mut end = None;
+
+            while idx < self.buffer.len() && self.buffer[idx].seq_number == start_seq + (idx - start) as i32 {
+                if self.buffer[idx].last {
+                    end = Some(idx);
+                    break;
+                }
+                idx += 1;
+            }
+
+            // a run that doesn't begin with a message's first packet is the
+            // tail of a message whose head is missing, not a whole message
+            if let (Some(end), true) = (end, self.buffer[start].first) {
+                let deadline = self.buffer[start..=end]
+                    .iter()
+                    .map(|p| p.release_time)
+                    .max()
+                    .unwrap();
+
+                if now >= deadline {
+                    let fragments: Vec<_> = self.buffer.drain(start..=end).collect();
+                    return Some(Self::reassemble(fragments));
+                }
+            }
+
+            idx = match end {
+                Some(end) => end + 1,
+                None => idx.max(start + 1),
+            };
+        }
+
+        None
+    }
+
+    /// Attempt to release the next deliverable message from the TSBPD
+    /// buffer, honoring `in_order_delivery`.
+    fn try_release(&mut self) -> Option<BytesMut> {
+        if self.in_order_delivery {
+            self.try_release_in_order()
+        } else {
+            self.try_release_out_of_order()
+        }
+    }
+
+    /// Send a NAK covering the given sequence numbers.
+    fn send_nak(&mut self, seqs: &[i32]) {
+        if seqs.is_empty() {
+            return;
+        }
+
+        let info = NakControlInfo::new(Self::compress_loss_list(seqs));
+        let nak = Packet::Control {
+            timestamp: self.sock.get_timestamp(),
+            dest_sockid: 0, // TODO: this should be better
+            control_type: ControlTypes::Nak(info),
+        };
+
+        self.sock.queue_sender.send((nak, self.remote)).unwrap();
+    }
 }
 
 impl Stream for Receiver {
@@ -75,25 +552,88 @@ impl Stream for Receiver {
             // in this section) and reset the associated time variables. For
             // ACK, also check the ACK packet interval.
 
-            if let Async::Ready(_) = self.ack_timer.poll()? {
-                // Send an ACK packet
-                let ack = Packet::Control {
-                    timestamp: self.sock.get_timestamp(),
-                    dest_sockid: 0, // TODO: this should be better
-                    control_type: ControlTypes::Ack(self.next_ack, AckControlInfo::new(self.lrsn)),
-                };
-                self.next_ack += 1;
+            if let Async::Ready(_) = self.full_ack_timer.poll()? {
+                // suppress the full ACK if nothing new has arrived since the
+                // last one, to avoid redundant control traffic
+                if self.lrsn != self.last_full_ack_lrsn {
+                    let now = self.sock.get_timestamp();
+                    let ack_num = self.next_ack;
+                    self.next_ack += 1;
+
+                    self.record_ack(ack_num, now);
+                    self.last_full_ack_lrsn = self.lrsn;
+
+                    let ack = Packet::Control {
+                        timestamp: now,
+                        dest_sockid: 0, // TODO: this should be better
+                        control_type: ControlTypes::Ack(
+                            ack_num,
+                            AckControlInfo::new_full(self.lrsn, self.rtt, self.rtt_var),
+                        ),
+                    };
+
+                    self.sock.queue_sender.send((ack, self.remote)).unwrap()
+                }
+            }
+
+            if let Async::Ready(_) = self.nak_timer.poll()? {
+                // re-feed any loss list entries that are older than our RTT
+                // estimate back into a fresh NAK
+                let now = self.sock.get_timestamp();
+                let mut to_nak = Vec::new();
 
-                self.sock.queue_sender.send((ack, self.remote)).unwrap()
+                for entry in &mut self.loss_list {
+                    if now - entry.feedback_time >= self.rtt {
+                        entry.feedback_time = now;
+                        entry.k += 1;
+
+                        to_nak.push(entry.seq_num);
+                    }
+                }
+
+                self.send_nak(&to_nak);
+            }
+
+            if let Async::Ready(_) = self.exp_timer.poll()? {
+                let now = self.sock.get_timestamp();
+
+                if self.exp_count == 0 {
+                    self.exp_run_start = now;
+                }
+                self.exp_count += 1;
+
+                if self.exp_count > EXP_DEAD_COUNT
+                    && now - self.exp_run_start >= EXP_DEAD_MIN_ELAPSED_US
+                {
+                    // the peer hasn't answered a keep-alive in a long time;
+                    // give up on the connection rather than hang forever
+                    return Err(Error::from(ErrorKind::TimedOut));
+                }
+
+                self.send_keep_alive();
+                self.exp_timer
+                    .reset(Self::exp_backoff(self.rtt, self.rtt_var, self.exp_count));
+            }
+
+            if let Async::Ready(_) = self.tsbpd_timer.poll()? {
+                // a buffered message's deadline may have passed, or a gap
+                // may have become overdue, even without new data arriving
+                if let Some(data) = self.try_release() {
+                    return Ok(Async::Ready(Some(data)));
+                }
             }
 
             // wait for a packet
-            // TODO: have some sort of set timeout and store EXPCount
             let (pack, addr) = match try_ready!(self.sock.poll()) {
                 Some(p) => p,
                 None => panic!(), // TODO: is this panic safe?
             };
 
+            // any packet from the peer means the connection is alive; reset EXP
+            self.exp_count = 0;
+            self.exp_timer
+                .reset(Self::exp_backoff(self.rtt, self.rtt_var, 0));
+
             // depending on the packet type, handle it
             match pack {
                 Packet::Control {
@@ -104,9 +644,61 @@ impl Stream for Receiver {
                     // handle the control packet
 
                     match control_type {
-                        &ControlTypes::Ack(seq_num, info) => unimplemented!(),
-                        &ControlTypes::Ack2(seq_num) => unimplemented!(),
-                        &ControlTypes::DropRequest(to_drop, info) => unimplemented!(),
+                        &ControlTypes::Ack(_seq_num, _info) => {
+                            // an Ack is addressed to the data sender, not us;
+                            // the analogous "don't trust an ack for more
+                            // than we sent" check lives on `Sender`
+                            // (see sender.rs). Nothing for the receiver to
+                            // do here, but a mislabeled/stray one shouldn't
+                            // take the whole connection down.
+                        }
+                        &ControlTypes::Ack2(seq_num) => {
+                            // reject an ACK2 for an ACK we never actually
+                            // sent; a spoofed or corrupted one here would
+                            // otherwise be able to poison the RTT estimator
+                            if seq_num > self.next_ack - 1 {
+                                return Err(Error::new(
+                                    ErrorKind::InvalidData,
+                                    "ack2'd an ack that was never sent",
+                                ));
+                            }
+
+                            // find the full ACK this is acknowledging and use
+                            // the round trip time as a new RTT sample; if
+                            // it's not there, it was already consumed or
+                            // evicted, which is fine, just not informative
+                            if let Some(pos) = self
+                                .ack_history_window
+                                .iter()
+                                .position(|&(ack_num, _)| ack_num == seq_num)
+                            {
+                                let (_, send_time) = self.ack_history_window.remove(pos);
+                                let sample = self.sock.get_timestamp() - send_time;
+
+                                self.sample_rtt(sample);
+                            }
+                        }
+                        &ControlTypes::DropRequest(_msg_num, ref info) => {
+                            // the peer has given up retransmitting this
+                            // range; stop waiting on it the same way a local
+                            // too-late-packet drop does
+                            self.loss_list
+                                .retain(|e| e.seq_num < info.first || e.seq_num > info.last);
+                            self.suspected_loss_list
+                                .retain(|&seq| seq < info.first || seq > info.last);
+
+                            if info.last > self.last_released_seq {
+                                self.last_released_seq = info.last;
+                            }
+
+                            // any buffered packet at or before `info.last`
+                            // is now behind last_released_seq; leaving it in
+                            // `buffer` would make try_release_in_order see
+                            // head_seq <= last_released_seq on its next
+                            // pass, call drop_through(first > last) (a
+                            // no-op), and spin forever on the same state
+                            self.buffer.retain(|p| p.seq_number > info.last);
+                        }
                         &ControlTypes::Handshake(info) => {
                             // just send it back
                             self.sock
@@ -114,7 +706,11 @@ impl Stream for Receiver {
                                 .send((pack.clone(), self.remote))
                                 .unwrap();
                         }
-                        &ControlTypes::KeepAlive => unimplemented!(),
+                        &ControlTypes::KeepAlive => {
+                            // nothing to do beyond the EXP reset above; the
+                            // keep-alive's only purpose is to prove the peer
+                            // is still there
+                        }
                         &ControlTypes::Nak(ref info) => unimplemented!(),
                         &ControlTypes::Shutdown => unimplemented!(),
                     }
@@ -128,9 +724,295 @@ impl Stream for Receiver {
                     dest_sockid,
                     payload,
                 } => {
-                    self.lrsn = seq_number;
+                    // if this fills a hole in the loss list, the retransmit
+                    // succeeded; stop asking for it
+                    if let Ok(idx) = self
+                        .loss_list
+                        .binary_search_by_key(&seq_number, |e| e.seq_num)
+                    {
+                        self.loss_list.remove(idx);
+                    } else if let Ok(idx) =
+                        self.suspected_loss_list.binary_search(&seq_number)
+                    {
+                        // it was just reordered, not lost; drop the suspicion
+                        // without ever NAKing it
+                        self.suspected_loss_list.remove(idx);
+                    } else if seq_number > self.lrsn + 1 {
+                        // one or more packets between lrsn and seq_number never
+                        // arrived; they're suspected lost until enough
+                        // higher-numbered packets arrive to rule out reordering
+                        for seq in (self.lrsn + 1)..seq_number {
+                            let pos = self
+                                .suspected_loss_list
+                                .binary_search(&seq)
+                                .unwrap_or_else(|e| e);
+
+                            self.suspected_loss_list.insert(pos, seq);
+                        }
+                    }
+
+                    if seq_number > self.lrsn {
+                        self.lrsn = seq_number;
+                    }
+
+                    self.packets_since_light_ack += 1;
+                    if self.packets_since_light_ack >= self.light_ack_packet_interval {
+                        self.packets_since_light_ack = 0;
+                        self.send_light_ack();
+                    }
+
+                    // promote any suspected-missing packets that have been
+                    // passed by enough higher sequence numbers into real loss
+                    let now = self.sock.get_timestamp();
+                    let threshold = self.reorder_threshold;
+                    let lrsn = self.lrsn;
+                    let mut newly_lost = Vec::new();
+
+                    self.suspected_loss_list.retain(|&seq| {
+                        if lrsn - seq >= threshold {
+                            newly_lost.push(seq);
+                            false
+                        } else {
+                            true
+                        }
+                    });
+
+                    for seq in &newly_lost {
+                        let pos = self
+                            .loss_list
+                            .binary_search_by_key(seq, |e| e.seq_num)
+                            .unwrap_or_else(|e| e);
+
+                        self.loss_list.insert(
+                            pos,
+                            LossListEntry {
+                                seq_num: *seq,
+                                feedback_time: now,
+                                k: 1,
+                            },
+                        );
+                    }
+
+                    self.send_nak(&newly_lost);
+
+                    self.in_order_delivery = in_order_delivery;
+
+                    // a duplicate retransmit of a packet we already buffered
+                    // or already released/dropped needs no further action
+                    if seq_number > self.last_released_seq {
+                        if let Err(pos) = self
+                            .buffer
+                            .binary_search_by_key(&seq_number, |p| p.seq_number)
+                        {
+                            self.buffer.insert(
+                                pos,
+                                BufferedPacket {
+                                    seq_number,
+                                    message_number,
+                                    first: message_loc.is_first(),
+                                    last: message_loc.is_last(),
+                                    release_time: timestamp + self.tsbpd_latency,
+                                    payload,
+                                },
+                            );
+                        }
+                    }
+
+                    if let Some(data) = self.try_release() {
+                        return Ok(Async::Ready(Some(data)));
+                    }
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc as std_mpsc;
+    use std::thread;
+
+    use futures::executor::spawn;
+    use futures::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+    use packet::MessageLocation;
+    use socket::{test as socket_test, Settings};
+
+    fn test_receiver(
+        init_seq_num: i32,
+        tsbpd_latency: Duration,
+    ) -> (
+        Receiver,
+        UnboundedSender<(Packet, SocketAddr)>,
+        UnboundedReceiver<(Packet, SocketAddr)>,
+    ) {
+        let settings = Settings {
+            tsbpd_latency,
+            init_seq_num,
+            reorder_threshold: None,
+            light_ack_packet_interval: None,
+            full_ack_interval: None,
+        };
+        let (sock, inject, sent) = socket_test::pair(settings);
+        let remote: SocketAddr = "127.0.0.1:0".parse().unwrap();
+
+        (Receiver::new(sock, remote), inject, sent)
+    }
+
+    fn data_packet(seq_number: i32, message_loc: MessageLocation, payload: &[u8]) -> Packet {
+        Packet::Data {
+            seq_number,
+            message_loc,
+            in_order_delivery: true,
+            message_number: 1,
+            timestamp: 0,
+            dest_sockid: 0,
+            payload: BytesMut::from(payload),
+        }
+    }
+
+    #[test]
+    fn head_of_line_drop_releases_next_message() {
+        let (receiver, inject, _sent) = test_receiver(100, Duration::from_millis(0));
+        let remote: SocketAddr = "127.0.0.1:0".parse().unwrap();
+
+        // seq 100 (the ISN) never arrives; seq 101 does, as a complete
+        // single-packet message. With tsbpd_latency at 0 its release
+        // deadline is already due, so the receiver should give up on 100
+        // once it's gap-detected and release 101 instead of waiting on it
+        // forever.
+        inject
+            .unbounded_send((data_packet(101, MessageLocation::Only, b"second"), remote))
+            .unwrap();
+
+        let released = spawn(receiver)
+            .wait_stream()
+            .expect("stream ended without releasing a message")
+            .expect("receiver returned an error");
+
+        assert_eq!(&released[..], b"second");
+    }
+
+    #[test]
+    fn message_reassembly_across_fragments() {
+        let (receiver, inject, _sent) = test_receiver(200, Duration::from_millis(0));
+        let remote: SocketAddr = "127.0.0.1:0".parse().unwrap();
+
+        inject
+            .unbounded_send((data_packet(200, MessageLocation::First, b"hel"), remote))
+            .unwrap();
+        inject
+            .unbounded_send((data_packet(201, MessageLocation::Middle, b"lo "), remote))
+            .unwrap();
+        inject
+            .unbounded_send((data_packet(202, MessageLocation::Last, b"world"), remote))
+            .unwrap();
+
+        let released = spawn(receiver)
+            .wait_stream()
+            .expect("stream ended without releasing a message")
+            .expect("receiver returned an error");
+
+        assert_eq!(&released[..], b"hello world");
+    }
+
+    #[test]
+    fn drop_request_prunes_buffer_instead_of_hanging() {
+        // regression test for a bug where an incoming DropRequest advanced
+        // last_released_seq past a packet still sitting in the buffer
+        // without removing it; once that packet's release deadline passed,
+        // try_release_in_order spun forever recomputing the same no-op
+        // drop_through(first > last) range instead of making progress.
+        let latency = Duration::from_millis(5);
+        let (receiver, inject, _sent) = test_receiver(300, latency);
+        let remote: SocketAddr = "127.0.0.1:0".parse().unwrap();
+
+        // 301 arrives out of order and isn't due yet, so it sits in the
+        // buffer rather than being resolved immediately.
+        inject
+            .unbounded_send((data_packet(301, MessageLocation::Only, b"late"), remote))
+            .unwrap();
+
+        // the peer gives up on everything through 301, including the
+        // packet already buffered above.
+        inject
+            .unbounded_send((
+                Packet::Control {
+                    timestamp: 0,
+                    dest_sockid: 0,
+                    control_type: ControlTypes::DropRequest(0, DropRequestInfo::new(300, 301)),
+                },
+                remote,
+            ))
+            .unwrap();
+
+        // a fresh, otherwise-unrelated message that should still be
+        // deliverable once its own deadline passes.
+        inject
+            .unbounded_send((data_packet(302, MessageLocation::Only, b"next"), remote))
+            .unwrap();
+
+        let (done_tx, done_rx) = std_mpsc::channel();
+        thread::spawn(move || {
+            let result = spawn(receiver).wait_stream();
+            done_tx.send(result).ok();
+        });
+
+        let result = done_rx
+            .recv_timeout(latency * 4)
+            .expect("poll() hung instead of pruning the dropped packet from the buffer");
+
+        let released = result
+            .expect("stream ended without releasing a message")
+            .expect("receiver returned an error");
+
+        assert_eq!(&released[..], b"next");
+    }
+
+    #[test]
+    fn compress_loss_list_singletons() {
+        assert_eq!(Receiver::compress_loss_list(&[1, 3, 5]), vec![1, 3, 5]);
+    }
+
+    #[test]
+    fn compress_loss_list_runs() {
+        assert_eq!(
+            Receiver::compress_loss_list(&[1, 2, 3, 7, 9, 10]),
+            vec![1 | 0x8000_0000u32 as i32, 3, 7, 9 | 0x8000_0000u32 as i32, 10]
+        );
+    }
+
+    #[test]
+    fn compress_loss_list_empty() {
+        assert_eq!(Receiver::compress_loss_list(&[]), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn exp_backoff_floor_before_any_rtt_sample() {
+        assert_eq!(
+            Receiver::exp_backoff(DEFAULT_RTT, 0, 0),
+            Duration::from_micros(MIN_EXP_INTERVAL_US.max(4 * DEFAULT_RTT) as u64)
+        );
+    }
+
+    #[test]
+    fn exp_backoff_doubles_and_caps() {
+        assert!(Receiver::exp_backoff(DEFAULT_RTT, 0, 1) > Receiver::exp_backoff(DEFAULT_RTT, 0, 0));
+        assert_eq!(Receiver::exp_backoff(DEFAULT_RTT, 0, 30), MAX_EXP_INTERVAL);
+    }
+
+    #[test]
+    fn ewma_rtt_first_sample_seeds_directly() {
+        let (rtt, rtt_var) = Receiver::ewma_rtt(DEFAULT_RTT, 0, false, 50_000);
+        assert_eq!(rtt, 50_000);
+        assert_eq!(rtt_var, 25_000);
+    }
+
+    #[test]
+    fn ewma_rtt_later_sample_smooths_towards_it() {
+        let (rtt, rtt_var) = Receiver::ewma_rtt(100_000, 10_000, true, 50_000);
+        assert_eq!(rtt, (7 * 100_000 + 50_000) / 8);
+        assert_eq!(rtt_var, (3 * 10_000 + 50_000) / 4);
+    }
+}
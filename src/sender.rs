@@ -0,0 +1,89 @@
+use std::io::{Error, ErrorKind};
+use std::net::SocketAddr;
+
+use futures::prelude::*;
+
+use packet::{ControlTypes, Packet};
+use socket::SrtSocket;
+
+/// The sending side of a connection: tracks the highest data sequence
+/// number actually transmitted, so an `Ack`/`Ack2` that acknowledges
+/// something never sent can be rejected instead of silently poisoning the
+/// retransmit/RTT bookkeeping.
+pub struct Sender {
+    sock: SrtSocket,
+    remote: SocketAddr,
+
+    /// the highest sequence number of a data packet actually sent so far
+    highest_seq_sent: i32,
+}
+
+impl Sender {
+    pub fn new(sock: SrtSocket, remote: SocketAddr, initial_seq_num: i32) -> Sender {
+        Sender {
+            sock,
+            remote,
+            highest_seq_sent: initial_seq_num - 1,
+        }
+    }
+
+    /// Queue a data packet for send. Updates `highest_seq_sent` so a later
+    /// Ack/Ack2 referencing it (or anything before it) is accepted, and
+    /// anything past it is rejected as unsent.
+    pub fn send_data(&mut self, packet: Packet) {
+        if let Packet::Data { seq_number, .. } = packet {
+            self.record_sent(seq_number);
+        }
+
+        self.sock.queue_sender.send((packet, self.remote)).unwrap();
+    }
+
+    fn record_sent(&mut self, seq_number: i32) {
+        if seq_number > self.highest_seq_sent {
+            self.highest_seq_sent = seq_number;
+        }
+    }
+
+    /// Validate an incoming `Ack`/`Ack2`, rejecting one that acknowledges a
+    /// sequence number this endpoint never actually sent. A peer has no
+    /// legitimate way to observe a sequence number past `highest_seq_sent`,
+    /// so one showing up here is spoofed or corrupted.
+    fn handle_control(&mut self, control_type: &ControlTypes) -> Result<(), Error> {
+        let acked = match *control_type {
+            ControlTypes::Ack(_, info) => info.lrsn,
+            ControlTypes::Ack2(seq_num) => seq_num,
+            _ => return Ok(()),
+        };
+
+        if acked > self.highest_seq_sent {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "acked a sequence number that was never sent",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Stream for Sender {
+    type Item = ();
+    type Error = Error;
+
+    /// Drains one incoming packet, routing a control packet through
+    /// `handle_control`. Yields `Some(())` after each packet processed, so a
+    /// caller can drive `Sender` and `Receiver` off the same event loop the
+    /// way `Receiver`'s own `poll` drives its control-packet handling.
+    fn poll(&mut self) -> Poll<Option<()>, Error> {
+        let (pack, _addr) = match try_ready!(self.sock.poll()) {
+            Some(p) => p,
+            None => return Ok(Async::Ready(None)),
+        };
+
+        if let Packet::Control { ref control_type, .. } = pack {
+            self.handle_control(control_type)?;
+        }
+
+        Ok(Async::Ready(Some(())))
+    }
+}
@@ -0,0 +1,168 @@
+use std::io::{Error, ErrorKind};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use futures::prelude::*;
+use futures::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use packet::Packet;
+
+/// Per-connection tuning. `Receiver` reads the overrides below instead of
+/// hardcoding its defaults. `SrtSocketBuilder` does not fill these in: its
+/// `connect` produces no socket.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    /// the latency exchanged during the handshake; see `tests/latency_exchange.rs`
+    pub tsbpd_latency: Duration,
+
+    /// the negotiated Initial Sequence Number
+    pub init_seq_num: i32,
+
+    /// overrides `receiver::REORDER_THRESHOLD` when set
+    pub reorder_threshold: Option<i32>,
+
+    /// overrides `receiver::DEFAULT_LIGHT_ACK_PACKET_INTERVAL` when set
+    pub light_ack_packet_interval: Option<i32>,
+
+    /// overrides `receiver::DEFAULT_FULL_ACK_INTERVAL` when set
+    pub full_ack_interval: Option<Duration>,
+}
+
+/// How a `SrtSocketBuilder` should establish its connection.
+pub enum ConnInitMethod {
+    Connect(SocketAddr),
+    Listen,
+}
+
+/// An established SRT connection's packet I/O: a `Stream` of inbound
+/// `(Packet, SocketAddr)`s, and `queue_sender` for outbound ones.
+/// `Receiver`/`Sender` are built on top of one of these.
+pub struct SrtSocket {
+    pub queue_sender: UnboundedSender<(Packet, SocketAddr)>,
+
+    incoming: UnboundedReceiver<(Packet, SocketAddr)>,
+    settings: Settings,
+    start: Instant,
+}
+
+impl SrtSocket {
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Microseconds since the connection was established, used to stamp
+    /// outgoing packets and to compare against buffered release times.
+    pub fn get_timestamp(&self) -> i32 {
+        self.start.elapsed().as_micros() as i32
+    }
+}
+
+impl Stream for SrtSocket {
+    type Item = (Packet, SocketAddr);
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Error> {
+        self.incoming
+            .poll()
+            .map_err(|()| Error::new(ErrorKind::Other, "incoming packet channel closed"))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    /// Build an `SrtSocket` around a pair of in-memory channels instead of a
+    /// real one, so `Receiver`/`Sender` logic can be driven without a UDP
+    /// socket or a real handshake. Returns the socket, a sender the test
+    /// uses to inject inbound packets, and a receiver the test uses to
+    /// observe what was queued for outbound send.
+    pub fn pair(
+        settings: Settings,
+    ) -> (
+        SrtSocket,
+        UnboundedSender<(Packet, SocketAddr)>,
+        UnboundedReceiver<(Packet, SocketAddr)>,
+    ) {
+        let (inject, incoming) = mpsc::unbounded();
+        let (queue_sender, sent) = mpsc::unbounded();
+
+        let sock = SrtSocket {
+            queue_sender,
+            incoming,
+            settings,
+            start: Instant::now(),
+        };
+
+        (sock, inject, sent)
+    }
+}
+
+/// Collects the settings for an SRT connection. `connect` performs no
+/// handshake: it always resolves to an error, and the tuning set here is not
+/// passed on to any `Settings`. See `tests/latency_exchange.rs` for
+/// `.latency()`.
+pub struct SrtSocketBuilder {
+    method: ConnInitMethod,
+    local_port: u16,
+    latency: Duration,
+    reorder_threshold: Option<i32>,
+    light_ack_packet_interval: Option<i32>,
+    full_ack_interval: Option<Duration>,
+}
+
+impl SrtSocketBuilder {
+    pub fn new(method: ConnInitMethod) -> SrtSocketBuilder {
+        SrtSocketBuilder {
+            method,
+            local_port: 0,
+            latency: Duration::from_secs(0),
+            reorder_threshold: None,
+            light_ack_packet_interval: None,
+            full_ack_interval: None,
+        }
+    }
+
+    pub fn local_port(mut self, port: u16) -> SrtSocketBuilder {
+        self.local_port = port;
+        self
+    }
+
+    pub fn latency(mut self, latency: Duration) -> SrtSocketBuilder {
+        self.latency = latency;
+        self
+    }
+
+    /// See `receiver::REORDER_THRESHOLD`.
+    pub fn reorder_threshold(mut self, reorder_threshold: i32) -> SrtSocketBuilder {
+        self.reorder_threshold = Some(reorder_threshold);
+        self
+    }
+
+    /// See `receiver::DEFAULT_LIGHT_ACK_PACKET_INTERVAL`.
+    pub fn light_ack_packet_interval(mut self, interval: i32) -> SrtSocketBuilder {
+        self.light_ack_packet_interval = Some(interval);
+        self
+    }
+
+    /// See `receiver::DEFAULT_FULL_ACK_INTERVAL`.
+    pub fn full_ack_interval(mut self, interval: Duration) -> SrtSocketBuilder {
+        self.full_ack_interval = Some(interval);
+        self
+    }
+
+    // TODO: this should actually perform the handshake described in
+    // https://tools.ietf.org/html/draft-gg-udt-03#page-12 over a real UDP
+    // socket, exchanging `latency` with the peer the way
+    // `tests/latency_exchange.rs` expects; that's its own project, not part
+    // of this tuning work.
+    #[allow(unused_variables)]
+    pub fn connect(self) -> impl Future<Item = SrtSocket, Error = Error> {
+        let SrtSocketBuilder { method, .. } = self;
+
+        future::err(Error::new(
+            ErrorKind::Other,
+            "SrtSocketBuilder::connect is not implemented in this tree",
+        ))
+    }
+}